@@ -12,12 +12,15 @@ mod prover {
     use alloc::vec::Vec;
     use phat_js::JsCode;
     use pink::{chain_extension::SigType, system::SystemRef};
-    use serde::Serialize;
+    use serde::{Deserialize, Serialize};
     use scale::{Decode, Encode};
     use ink::codegen::Env;
+    use chacha20poly1305::{aead::{Aead, KeyInit}, Key, XChaCha20Poly1305, XNonce};
 
     use ink::storage::Mapping;
 
+    #[derive(Encode, Decode, Debug)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     struct Hexed<T>(T);
 
     impl<T> From<T> for Hexed<T> {
@@ -32,6 +35,18 @@ mod prover {
         }
     }
 
+    impl<'de, T: TryFrom<Vec<u8>>> serde::Deserialize<'de> for Hexed<T> {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let s = String::deserialize(deserializer)?;
+            let bytes = hex::decode(s.trim_start_matches("0x"))
+                .map_err(serde::de::Error::custom)?;
+            let len = bytes.len();
+            T::try_from(bytes)
+                .map(Hexed)
+                .map_err(|_| serde::de::Error::custom(format!("unexpected byte length {len}")))
+        }
+    }
+
     #[derive(Encode, Decode, Debug)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub enum Error {
@@ -41,11 +56,14 @@ mod prover {
         BadConfig,
         #[codec(index = 3)]
         JsError(String),
+        #[codec(index = 4)]
+        InvalidProof,
     }
 
     type Result<T, E=Error> = core::result::Result<T, E>;
 
-    #[derive(Serialize)]
+    #[derive(Encode, Decode, Debug, Serialize, Deserialize)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     /// Struct representing the signed payload.
     pub struct ProvenPayload {
         output: String,
@@ -54,9 +72,13 @@ mod prover {
         contract_code_hash: Hexed<Hash>,
         contract_address: Hexed<AccountId>,
         block_number: u32,
+        /// Caller-supplied nonce binding this proof to a specific request, so a captured
+        /// `ProvenOutput` can't be replayed against a different verifier. `None` when the
+        /// caller didn't ask for replay protection.
+        challenge: Option<Hexed<[u8; 32]>>,
     }
 
-    #[derive(Encode, Decode, Debug)]
+    #[derive(Encode, Decode, Debug, Clone)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     /// Struct representing the output of a proven execution.
     pub struct ProvenOutput {
@@ -65,10 +87,141 @@ mod prover {
         pubkey: Vec<u8>,
     }
 
+    #[derive(Encode, Decode, Debug)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    /// A single step of a [`JsProver::run_pipeline`] call.
+    pub struct PipelineStep {
+        js_code: String,
+        args: Vec<String>,
+        secret: Option<String>,
+    }
+
+    /// EIP-712 typed-data encoding of [`ProvenPayload`], used so a Solidity verifier can
+    /// `ecrecover` the signer of a [`JsProver::run_js_evm`] proof without re-implementing
+    /// the contract's JSON serialization.
+    mod eip712 {
+        use super::{AccountId, Hash, ProvenPayload};
+        use alloc::vec::Vec;
+        use ink::env::hash::{HashOutput, Keccak256};
+
+        /// The `EIP712Domain` type string; `domain_separator` hashes it with `keccak256`
+        /// to get the domain type hash.
+        const DOMAIN_TYPE_HASH: &str =
+            "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+        /// `ProvenPayload(string output,bytes jsCodeHash,bytes jsEngineCodeHash,bytes contractCodeHash,bytes contractAddress,uint256 blockNumber,bytes32 challenge)`
+        ///
+        /// `contractAddress` is encoded as `bytes` rather than `address` because it is the
+        /// prover's 32-byte Substrate account id, not a 20-byte EVM address.
+        const PAYLOAD_TYPE: &str = "ProvenPayload(string output,bytes jsCodeHash,bytes jsEngineCodeHash,bytes contractCodeHash,bytes contractAddress,uint256 blockNumber,bytes32 challenge)";
+
+        fn keccak256(data: &[u8]) -> [u8; 32] {
+            let mut output = <Keccak256 as HashOutput>::Type::default();
+            ink::env::hash_bytes::<Keccak256>(data, &mut output);
+            output
+        }
+
+        /// Left-pads `bytes` into the low-order end of a 32-byte EVM word.
+        fn pad32(bytes: &[u8]) -> [u8; 32] {
+            let mut word = [0u8; 32];
+            let start = 32 - bytes.len();
+            word[start..].copy_from_slice(bytes);
+            word
+        }
+
+        fn address_word(address: &[u8; 20]) -> [u8; 32] {
+            pad32(address)
+        }
+
+        fn domain_separator(chain_id: u64, verifying_contract: &[u8; 20]) -> [u8; 32] {
+            let mut encoded = Vec::with_capacity(32 * 5);
+            encoded.extend_from_slice(&keccak256(DOMAIN_TYPE_HASH.as_bytes()));
+            encoded.extend_from_slice(&keccak256(b"GptProver"));
+            encoded.extend_from_slice(&keccak256(b"1"));
+            encoded.extend_from_slice(&pad32(&chain_id.to_be_bytes()));
+            encoded.extend_from_slice(&address_word(verifying_contract));
+            keccak256(&encoded)
+        }
+
+        fn hash_struct(payload: &ProvenPayload) -> [u8; 32] {
+            let contract_code_hash: Hash = payload.contract_code_hash.0;
+            let js_engine_code_hash: AccountId = payload.js_engine_code_hash.0;
+            let contract_address: AccountId = payload.contract_address.0;
+
+            let mut encoded = Vec::with_capacity(32 * 7);
+            encoded.extend_from_slice(&keccak256(PAYLOAD_TYPE.as_bytes()));
+            encoded.extend_from_slice(&keccak256(payload.output.as_bytes()));
+            encoded.extend_from_slice(&keccak256(payload.js_code_hash.0.as_ref()));
+            encoded.extend_from_slice(&keccak256(js_engine_code_hash.as_ref()));
+            encoded.extend_from_slice(&keccak256(contract_code_hash.as_ref()));
+            encoded.extend_from_slice(&keccak256(contract_address.as_ref()));
+            encoded.extend_from_slice(&pad32(&(payload.block_number as u64).to_be_bytes()));
+            encoded.extend_from_slice(&payload.challenge.as_ref().map(|c| c.0).unwrap_or([0u8; 32]));
+            keccak256(&encoded)
+        }
+
+        /// Computes `keccak256(0x19 01 ‖ domainSeparator ‖ hashStruct(payload))`.
+        pub fn digest(payload: &ProvenPayload, chain_id: u64, verifying_contract: &[u8; 20]) -> [u8; 32] {
+            let mut preimage = Vec::with_capacity(2 + 32 + 32);
+            preimage.extend_from_slice(&[0x19, 0x01]);
+            preimage.extend_from_slice(&domain_separator(chain_id, verifying_contract));
+            preimage.extend_from_slice(&hash_struct(payload));
+            keccak256(&preimage)
+        }
+
+        #[cfg(test)]
+        mod tests {
+            use super::*;
+            use super::super::Hexed;
+
+            #[test]
+            fn digest_matches_independently_computed_reference() {
+                // Fixed field values, chosen arbitrarily, with the resulting digest computed
+                // by an independent (Python) implementation of Keccak-256 and the same
+                // EIP-712 struct encoding, to pin the field ordering, left-padding and
+                // static/dynamic ABI encoding this module hand-rolls.
+                let payload = ProvenPayload {
+                    output: "42".to_string(),
+                    js_code_hash: Hexed(Hash::from([0x11u8; 32])),
+                    js_engine_code_hash: Hexed(AccountId::from([0x22u8; 32])),
+                    contract_code_hash: Hexed(Hash::from([0x33u8; 32])),
+                    contract_address: Hexed(AccountId::from([0x44u8; 32])),
+                    block_number: 7,
+                    challenge: Some(Hexed([0x55u8; 32])),
+                };
+                let verifying_contract = [0x66u8; 20];
+
+                let got = digest(&payload, 1, &verifying_contract);
+
+                let expected: [u8; 32] = [
+                    0x97, 0x1d, 0xb0, 0x4f, 0x38, 0x4a, 0x34, 0x04, 0xc8, 0x02, 0xf9, 0x6e, 0x99,
+                    0xf8, 0x97, 0x6a, 0x4d, 0xab, 0xf5, 0x9b, 0x9b, 0xbb, 0x0f, 0xcd, 0x4b, 0x46,
+                    0x36, 0x8f, 0xf7, 0x51, 0x72, 0xa1,
+                ];
+                assert_eq!(got, expected);
+            }
+        }
+    }
+
+    #[derive(Encode, Decode, Debug)]
+    /// A secret encrypted at rest under the contract's derived encryption key.
+    struct EncryptedSecret {
+        nonce: Vec<u8>,
+        ciphertext: Vec<u8>,
+    }
+
     #[ink(storage)]
     pub struct JsProver {
         owner: AccountId,
-        secrets: Mapping<Hash, String>,
+        secrets: Mapping<Hash, EncryptedSecret>,
+        /// Tracks every key ever inserted into `secrets`, since `Mapping` can't be iterated;
+        /// needed by `rotate_secret_key` to re-encrypt every stored entry.
+        secret_hashes: Vec<Hash>,
+        /// Bumped by `rotate_secret_key` and folded into the encryption key derivation so a
+        /// rotation invalidates the old key without needing a separately stored salt.
+        secret_key_epoch: u32,
+        /// Memoizes `run_js` proofs, keyed by a hash of the code, args, secret and challenge
+        /// used to produce them, alongside the block they were produced at.
+        cache: Mapping<Hash, (ProvenOutput, u32)>,
     }
 
     impl JsProver {
@@ -77,6 +230,9 @@ mod prover {
             Self {
                 owner: Self::env().caller(),
                 secrets: Mapping::new(),
+                secret_hashes: Vec::new(),
+                secret_key_epoch: 0,
+                cache: Mapping::new(),
             }
         }
     }
@@ -96,17 +252,108 @@ mod prover {
         /// * `js_code` - The Javascript code to run.
         /// * `args` - The arguments to pass to the Javascript code.
         /// * `secret` - The secretData passed to the JavaScript code as global variable `secretData`.
+        /// * `challenge` - An optional caller-supplied nonce folded into the signed payload so
+        ///   the returned proof can't be replayed against a different verifier. Callers that
+        ///   need replay resistance should pass a fresh random value per request.
+        /// * `max_age_blocks` - If set, and a proof for the same code, args, secret and
+        ///   challenge was produced within this many blocks, that cached proof is returned
+        ///   instead of re-running the (potentially expensive, non-deterministic) JavaScript.
         ///
         /// @ui js_code widget codemirror
         /// @ui js_code options.lang javascript
         #[ink(message)]
         pub fn run_js(
+            &mut self,
+            js_code: String,
+            args: Vec<String>,
+            secret: Option<String>,
+            challenge: Option<[u8; 32]>,
+            max_age_blocks: Option<u32>,
+        ) -> Result<ProvenOutput> {
+            self.do_run_js(js_code, args, secret, challenge, max_age_blocks)
+        }
+
+        /// Proves the output of a JavaScript code execution in a form an EVM contract can
+        /// verify trustlessly with `ecrecover`, instead of the Sr25519 format returned by
+        /// [`Self::run_js`].
+        ///
+        /// The payload is hashed and signed as EIP-712 typed data
+        /// (`ProvenPayload` over the `GptProver` domain) using a secp256k1 key, and the
+        /// signature is returned in the 65-byte `r‖s‖v` recoverable form.
+        ///
+        /// # Arguments
+        ///
+        /// * `js_code` - The Javascript code to run.
+        /// * `args` - The arguments to pass to the Javascript code.
+        /// * `secret` - The secretData passed to the JavaScript code as global variable `secretData`.
+        /// * `chain_id` - The EVM chain id of the verifying contract, used in the EIP-712 domain.
+        /// * `verifying_contract` - The address of the EVM contract that will `ecrecover` this proof.
+        /// * `challenge` - An optional caller-supplied nonce, see [`Self::run_js`].
+        ///
+        /// @ui js_code widget codemirror
+        /// @ui js_code options.lang javascript
+        #[ink(message)]
+        pub fn run_js_evm(
             &self,
             js_code: String,
             args: Vec<String>,
             secret: Option<String>,
+            chain_id: u64,
+            verifying_contract: [u8; 20],
+            challenge: Option<[u8; 32]>,
         ) -> Result<ProvenOutput> {
-            self.do_run_js(js_code, args, secret)
+            self.do_run_js_evm(js_code, args, secret, chain_id, verifying_contract, challenge)
+        }
+
+        /// Proves the output of a multi-step pipeline, where each step's string output is
+        /// passed as an extra argument to the next step.
+        ///
+        /// The resulting payload commits to the whole pipeline: its `js_code_hash` is a
+        /// Blake2 digest over every step's individual code hash, in order, and `output` is
+        /// the last step's result. This lets callers prove composed workflows (fetch,
+        /// transform, summarize, ...) as a single attested unit instead of chaining separate
+        /// `run_js` proofs and re-trusting the intermediate outputs.
+        ///
+        /// # Arguments
+        ///
+        /// * `steps` - The ordered pipeline steps to run.
+        /// * `challenge` - An optional caller-supplied nonce, see [`Self::run_js`].
+        #[ink(message)]
+        pub fn run_pipeline(
+            &self,
+            steps: Vec<PipelineStep>,
+            challenge: Option<[u8; 32]>,
+        ) -> Result<ProvenOutput> {
+            self.do_run_pipeline(steps, challenge)
+        }
+
+        /// Verifies a [`ProvenOutput`] produced by [`Self::run_js`] or [`Self::run_pipeline`]
+        /// and returns the decoded payload if the signature checks out.
+        ///
+        /// This lets another contract or an off-chain client confirm a proof originated from
+        /// this prover instance without re-implementing the payload serialization and key
+        /// derivation. It only covers proofs signed over the raw payload bytes, i.e. those
+        /// returned by [`Self::run_js`] and [`Self::run_pipeline`]; EVM proofs from [`Self::run_js_evm`] are signed over
+        /// an EIP-712 digest and are meant to be checked on-chain with `ecrecover` instead.
+        #[ink(message)]
+        pub fn verify(&self, proof: ProvenOutput) -> Result<ProvenPayload> {
+            // Only the Sr25519 format signed over the raw payload bytes (run_js) is covered
+            // here; run_js_evm proofs are signed over an EIP-712 digest instead and must be
+            // checked on-chain with ecrecover, so any pubkey other than this instance's
+            // Sr25519 pubkey is rejected.
+            if proof.pubkey != self.pubkey() {
+                return Err(Error::InvalidProof);
+            }
+            let ok = pink::ext().verify(
+                SigType::Sr25519,
+                &proof.pubkey,
+                proof.payload.as_bytes(),
+                &proof.signature,
+            );
+            if !ok {
+                return Err(Error::InvalidProof);
+            }
+            pink_json::from_str(&proof.payload).map_err(|_| Error::InvalidProof)
         }
     }
 
@@ -120,11 +367,45 @@ mod prover {
             Ok(())
         }
 
-        /// Updates the secret data.
+        /// Updates the secret data. The value is encrypted before being written to storage.
         #[ink(message)]
         pub fn set_secret(&mut self, for_code_hash: Hash, secret: String) -> Result<()> {
             self.ensure_owner()?;
-            self.secrets.insert(for_code_hash, &secret);
+            if self.secrets.get(for_code_hash).is_none() {
+                self.secret_hashes.push(for_code_hash);
+            }
+            let encrypted = self.encrypt_secret(&for_code_hash, &secret);
+            self.secrets.insert(for_code_hash, &encrypted);
+            Ok(())
+        }
+
+        /// Re-encrypts every stored secret under a freshly derived key, invalidating the
+        /// previous one. Use this if the contract's derived key material is ever suspected
+        /// of leaking.
+        #[ink(message)]
+        pub fn rotate_secret_key(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            let plaintexts: Vec<(Hash, String)> = self
+                .secret_hashes
+                .iter()
+                .map(|hash| {
+                    let encrypted = self.secrets.get(hash).expect("tracked hash must be present");
+                    (*hash, self.decrypt_secret(&encrypted))
+                })
+                .collect();
+            self.secret_key_epoch += 1;
+            for (hash, secret) in plaintexts {
+                let encrypted = self.encrypt_secret(&hash, &secret);
+                self.secrets.insert(hash, &encrypted);
+            }
+            Ok(())
+        }
+
+        /// Drops every memoized `run_js` proof, forcing the next matching call to recompute.
+        #[ink(message)]
+        pub fn clear_cache(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            self.cache = Mapping::new();
             Ok(())
         }
     }
@@ -142,15 +423,63 @@ mod prover {
         }
 
         fn secret_data(&self, code_hash: &Hash) -> String {
-            self.secrets.get(code_hash).unwrap_or_default()
+            self.secrets
+                .get(code_hash)
+                .map(|encrypted| self.decrypt_secret(&encrypted))
+                .unwrap_or_default()
         }
 
-        pub fn do_run_js(
+        fn ecdsa_key(&self) -> Vec<u8> {
+            pink::ext().derive_sr25519_key(b"evm-signer"[..].into())
+        }
+
+        /// Derives the symmetric key used to encrypt secrets at rest. Folding in
+        /// `secret_key_epoch` means `rotate_secret_key` can invalidate the old key just by
+        /// bumping a counter, without storing a salt anyone with read access could see.
+        fn secret_encryption_key(&self) -> [u8; 32] {
+            let seed = pink::ext().derive_sr25519_key(
+                format!("secret-enc-{}", self.secret_key_epoch).into_bytes().into(),
+            );
+            self.env()
+                .hash_bytes::<ink::env::hash::Blake2x256>(&seed)
+        }
+
+        fn encrypt_secret(&self, for_code_hash: &Hash, secret: &str) -> EncryptedSecret {
+            let key = Key::from_slice(&self.secret_encryption_key());
+            let cipher = XChaCha20Poly1305::new(key);
+            let nonce_material: [u8; 32] = self
+                .env()
+                .hash_bytes::<ink::env::hash::Blake2x256>(
+                    &[for_code_hash.as_ref(), secret.as_bytes()].concat(),
+                );
+            let nonce = XNonce::from_slice(&nonce_material[..24]);
+            let ciphertext = cipher
+                .encrypt(nonce, secret.as_bytes())
+                .expect("secret encryption should not fail");
+            EncryptedSecret {
+                nonce: nonce.to_vec(),
+                ciphertext,
+            }
+        }
+
+        fn decrypt_secret(&self, encrypted: &EncryptedSecret) -> String {
+            let key = Key::from_slice(&self.secret_encryption_key());
+            let cipher = XChaCha20Poly1305::new(key);
+            let nonce = XNonce::from_slice(&encrypted.nonce);
+            let plaintext = cipher
+                .decrypt(nonce, encrypted.ciphertext.as_ref())
+                .expect("secret decryption should not fail; was the key rotated without re-encrypting?");
+            String::from_utf8(plaintext).expect("stored secret must be valid utf-8")
+        }
+
+        /// Runs `js_code` with `args` and the resolved `secretData`, returning the code's
+        /// hash and its string output.
+        fn execute(
             &self,
             js_code: String,
             args: Vec<String>,
-            secret: Option<String>
-        ) -> Result<ProvenOutput> {
+            secret: Option<String>,
+        ) -> Result<(Hash, String)> {
             use phat_js as js;
             let js_code_hash: Hash = self
                 .env()
@@ -170,15 +499,25 @@ mod prover {
                 JsCode::Source(js_code),
             ];
             let output = pink::ext().js_eval(codes, args);
-            let output = match output {
-                js::JsValue::String(s) => s,
-                _ => return Err(Error::JsError(format!("Invalid output: {:?}", output))),
-            };
-            let key = self.key();
+            match output {
+                js::JsValue::String(s) => Ok((js_code_hash, s)),
+                _ => Err(Error::JsError(format!("Invalid output: {:?}", output))),
+            }
+        }
+
+        /// Builds a [`ProvenPayload`] committing to `js_code_hash` (a single code hash for
+        /// [`Self::run_js`]/[`Self::run_js_evm`], or an aggregate digest of every step's hash
+        /// for [`Self::run_pipeline`]) and the execution's final `output`.
+        fn assemble_payload(
+            &self,
+            js_code_hash: Hash,
+            output: String,
+            challenge: Option<[u8; 32]>,
+        ) -> ProvenPayload {
             let driver = SystemRef::instance()
                 .get_driver("JsRuntime".into())
                 .expect("Failed to get Js driver");
-            let payload = ProvenPayload {
+            ProvenPayload {
                 js_code_hash: js_code_hash.into(),
                 js_engine_code_hash: driver.into(),
                 contract_code_hash: self
@@ -187,10 +526,140 @@ mod prover {
                     .expect("Failed to get contract code hash").into(),
                 contract_address: self.env().account_id().into(),
                 block_number: self.env().block_number(),
+                challenge: challenge.map(Hexed),
                 output,
+            }
+        }
+
+        /// Runs `js_code` and builds the unsigned [`ProvenPayload`] describing its execution.
+        fn build_payload(
+            &self,
+            js_code: String,
+            args: Vec<String>,
+            secret: Option<String>,
+            challenge: Option<[u8; 32]>,
+        ) -> Result<ProvenPayload> {
+            let (js_code_hash, output) = self.execute(js_code, args, secret)?;
+            Ok(self.assemble_payload(js_code_hash, output, challenge))
+        }
+
+        /// Blake2-hashes the ordered list of per-step code hashes produced by
+        /// [`Self::run_pipeline`] into a single digest the aggregate proof commits to.
+        fn aggregate_step_hashes(&self, step_hashes: &[Hash]) -> Hash {
+            let mut data = Vec::with_capacity(step_hashes.len() * 32);
+            for hash in step_hashes {
+                data.extend_from_slice(hash.as_ref());
+            }
+            self.env()
+                .hash_bytes::<ink::env::hash::Blake2x256>(&data)
+                .into()
+        }
+
+        fn cache_key(
+            &self,
+            js_code_hash: &Hash,
+            args: &[String],
+            secret: &str,
+            challenge: Option<[u8; 32]>,
+        ) -> Hash {
+            let secret_hash: [u8; 32] = self
+                .env()
+                .hash_bytes::<ink::env::hash::Blake2x256>(secret.as_bytes());
+            let mut data = Vec::new();
+            data.extend_from_slice(js_code_hash.as_ref());
+            data.extend_from_slice(&args.encode());
+            data.extend_from_slice(&secret_hash);
+            data.extend_from_slice(&challenge.encode());
+            self.env().hash_bytes::<ink::env::hash::Blake2x256>(&data).into()
+        }
+
+        pub fn do_run_js(
+            &mut self,
+            js_code: String,
+            args: Vec<String>,
+            secret: Option<String>,
+            challenge: Option<[u8; 32]>,
+            max_age_blocks: Option<u32>,
+        ) -> Result<ProvenOutput> {
+            let js_code_hash: Hash = self
+                .env()
+                .hash_bytes::<ink::env::hash::Blake2x256>(js_code.as_bytes())
+                .into();
+            let resolved_secret = match secret {
+                Some(ref s) => s.clone(),
+                None => self.secret_data(&js_code_hash),
+            };
+            let cache_key = self.cache_key(&js_code_hash, &args, &resolved_secret, challenge);
+
+            if let Some(max_age) = max_age_blocks {
+                if let Some((cached, produced_at)) = self.cache.get(cache_key) {
+                    if self.env().block_number().saturating_sub(produced_at) <= max_age {
+                        return Ok(cached);
+                    }
+                }
+            }
+
+            let payload = self.build_payload(js_code, args, Some(resolved_secret), challenge)?;
+            let payload_str = pink_json::to_string(&payload).expect("Failed to serialize payload");
+            let signature = pink::ext().sign(SigType::Sr25519, &self.key(), payload_str.as_bytes());
+            let output = ProvenOutput {
+                payload: payload_str,
+                signature,
+                pubkey: self.pubkey(),
             };
+
+            if max_age_blocks.is_some() {
+                self.cache
+                    .insert(cache_key, &(output.clone(), self.env().block_number()));
+            }
+            Ok(output)
+        }
+
+        pub fn do_run_js_evm(
+            &self,
+            js_code: String,
+            args: Vec<String>,
+            secret: Option<String>,
+            chain_id: u64,
+            verifying_contract: [u8; 20],
+            challenge: Option<[u8; 32]>,
+        ) -> Result<ProvenOutput> {
+            let payload = self.build_payload(js_code, args, secret, challenge)?;
+            let payload_str = pink_json::to_string(&payload).expect("Failed to serialize payload");
+            let digest = eip712::digest(&payload, chain_id, &verifying_contract);
+            let key = self.ecdsa_key();
+            let signature = pink::ext().sign(SigType::Ecdsa, &key, &digest);
+            Ok(ProvenOutput {
+                payload: payload_str,
+                signature,
+                pubkey: pink::ext().get_public_key(SigType::Ecdsa, &key),
+            })
+        }
+
+        pub fn do_run_pipeline(
+            &self,
+            steps: Vec<PipelineStep>,
+            challenge: Option<[u8; 32]>,
+        ) -> Result<ProvenOutput> {
+            if steps.is_empty() {
+                return Err(Error::BadConfig);
+            }
+            let mut step_hashes = Vec::with_capacity(steps.len());
+            let mut output = None;
+            for step in steps {
+                let mut args = step.args;
+                if let Some(prev_output) = output.take() {
+                    args.push(prev_output);
+                }
+                let (js_code_hash, step_output) = self.execute(step.js_code, args, step.secret)?;
+                step_hashes.push(js_code_hash);
+                output = Some(step_output);
+            }
+            let output = output.expect("steps is non-empty, so at least one step ran");
+            let aggregate_hash = self.aggregate_step_hashes(&step_hashes);
+            let payload = self.assemble_payload(aggregate_hash, output, challenge);
             let payload_str = pink_json::to_string(&payload).expect("Failed to serialize payload");
-            let signature = pink::ext().sign(SigType::Sr25519, &key, &payload_str.as_bytes());
+            let signature = pink::ext().sign(SigType::Sr25519, &self.key(), payload_str.as_bytes());
             Ok(ProvenOutput {
                 payload: payload_str,
                 signature,
@@ -201,12 +670,14 @@ mod prover {
 
     #[cfg(test)]
     mod tests {
+        use super::{Error, PipelineStep, ProvenOutput};
         use super::JsProverRef;
 
         use alloc::vec;
         use pink_drink::{PinkRuntime, SessionExt, DeployBundle, Callable};
         use drink::session::Session;
         use ink::codegen::TraitCallBuilder;
+        use sp_core::Pair;
 
         #[test]
         fn run_js_works() -> Result<(), Box<dyn std::error::Error>> {
@@ -236,8 +707,8 @@ mod prover {
             let model = "gpt-3.5-turbo-0125".to_string();
             let prompt = "What is the meaning of life?".to_string();
             let result = contract_ref
-                .call()
-                .run_js(js_code.into(), vec![model, prompt], None)
+                .call_mut()
+                .run_js(js_code.into(), vec![model, prompt], None, None, None)
                 .query(&mut session)?;
             let output = result.unwrap().payload;
             println!("output: {}", output);
@@ -250,10 +721,164 @@ mod prover {
                 Sidevm.inspect('Reply:', reply);
             "#;
             let _result = contract_ref
-                .call()
-                .run_js(js_code.into(), vec![output], None)
+                .call_mut()
+                .run_js(js_code.into(), vec![output], None, None, None)
                 .query(&mut session)?;
             Ok(())
         }
+
+        #[test]
+        fn verify_rejects_proof_from_a_foreign_key() -> Result<(), Box<dyn std::error::Error>> {
+            let contract_code = include_bytes!("./target/ink/js_prover.wasm");
+
+            let mut session = Session::<PinkRuntime>::new()?;
+            session.set_driver("JsRuntime", &[0u8; 32])?;
+            let mut contract_ref = JsProverRef::default().deploy_wasm(contract_code, &mut session)?;
+
+            let js_code = "scriptArgs[0]".to_string();
+            let own_proof = contract_ref
+                .call_mut()
+                .run_js(js_code, vec!["hello".to_string()], None, None, None)
+                .query(&mut session)?
+                .unwrap();
+
+            // A proof actually produced by this instance verifies.
+            contract_ref
+                .call()
+                .verify(own_proof.clone())
+                .query(&mut session)??;
+
+            // An attacker who signs the very same payload bytes with their own keypair must
+            // not be able to pass it off as a proof from this prover instance.
+            let (forger, _seed) = sp_core::sr25519::Pair::generate();
+            let signature = forger.sign(own_proof.payload.as_bytes()).0.to_vec();
+            let forged = ProvenOutput {
+                payload: own_proof.payload,
+                signature,
+                pubkey: forger.public().0.to_vec(),
+            };
+            let verified = contract_ref.call().verify(forged).query(&mut session)?;
+            assert!(matches!(verified, Err(Error::InvalidProof)));
+            Ok(())
+        }
+
+        #[test]
+        fn rotate_secret_key_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+            let contract_code = include_bytes!("./target/ink/js_prover.wasm");
+
+            let mut session = Session::<PinkRuntime>::new()?;
+            session.set_driver("JsRuntime", &[0u8; 32])?;
+            let mut contract_ref = JsProverRef::default().deploy_wasm(contract_code, &mut session)?;
+
+            let js_code = "secretData".to_string();
+            let js_code_hash = sp_core::blake2_256(js_code.as_bytes());
+            contract_ref
+                .call_mut()
+                .set_secret(js_code_hash.into(), "top-secret".to_string())
+                .submit_tx(&mut session)?
+                .unwrap();
+
+            contract_ref
+                .call_mut()
+                .rotate_secret_key()
+                .submit_tx(&mut session)?
+                .unwrap();
+
+            // The secret must still decrypt correctly after rotation re-encrypted it under
+            // the new derived key.
+            let result = contract_ref
+                .call_mut()
+                .run_js(js_code, vec![], None, None, None)
+                .query(&mut session)?
+                .unwrap();
+            assert!(result.payload.contains("top-secret"));
+            Ok(())
+        }
+
+        #[test]
+        fn run_js_cache_is_keyed_by_challenge() -> Result<(), Box<dyn std::error::Error>> {
+            let contract_code = include_bytes!("./target/ink/js_prover.wasm");
+
+            let mut session = Session::<PinkRuntime>::new()?;
+            session.set_driver("JsRuntime", &[0u8; 32])?;
+            let mut contract_ref = JsProverRef::default().deploy_wasm(contract_code, &mut session)?;
+
+            let js_code = "scriptArgs[0]".to_string();
+            let challenge_a = [1u8; 32];
+            let challenge_b = [2u8; 32];
+
+            let first = contract_ref
+                .call_mut()
+                .run_js(
+                    js_code.clone(),
+                    vec!["hi".to_string()],
+                    None,
+                    Some(challenge_a),
+                    Some(100),
+                )
+                .query(&mut session)?
+                .unwrap();
+
+            // A fresh challenge must not be served the entry cached under a different one.
+            let second = contract_ref
+                .call_mut()
+                .run_js(
+                    js_code.clone(),
+                    vec!["hi".to_string()],
+                    None,
+                    Some(challenge_b),
+                    Some(100),
+                )
+                .query(&mut session)?
+                .unwrap();
+            assert_ne!(first.payload, second.payload);
+
+            // Repeating the original request is served from cache.
+            let third = contract_ref
+                .call_mut()
+                .run_js(js_code, vec!["hi".to_string()], None, Some(challenge_a), Some(100))
+                .query(&mut session)?
+                .unwrap();
+            assert_eq!(first.payload, third.payload);
+            Ok(())
+        }
+
+        #[test]
+        fn run_pipeline_chains_outputs_and_rejects_empty_steps() -> Result<(), Box<dyn std::error::Error>> {
+            let contract_code = include_bytes!("./target/ink/js_prover.wasm");
+
+            let mut session = Session::<PinkRuntime>::new()?;
+            session.set_driver("JsRuntime", &[0u8; 32])?;
+            let contract_ref = JsProverRef::default().deploy_wasm(contract_code, &mut session)?;
+
+            // An empty pipeline has nothing to aggregate a proof over.
+            let empty = contract_ref.call().run_pipeline(vec![], None).query(&mut session)?;
+            assert!(matches!(empty, Err(Error::BadConfig)));
+
+            // Each step's output is threaded into the next step's args, and the final
+            // output is the last step's result.
+            let steps = vec![
+                PipelineStep {
+                    js_code: "scriptArgs[0] + '-a'".to_string(),
+                    args: vec!["seed".to_string()],
+                    secret: None,
+                },
+                PipelineStep {
+                    js_code: "scriptArgs[0] + '-b'".to_string(),
+                    args: vec![],
+                    secret: None,
+                },
+            ];
+            let proof = contract_ref
+                .call()
+                .run_pipeline(steps, None)
+                .query(&mut session)?
+                .unwrap();
+            assert!(proof.payload.contains("seed-a-b"));
+
+            // The aggregate signature is still a proof this instance can verify.
+            contract_ref.call().verify(proof).query(&mut session)??;
+            Ok(())
+        }
     }
 }